@@ -0,0 +1,333 @@
+//! Port of Google's CityHash128, version 1.0.2.
+//!
+//! ClickHouse pins this exact revision (prior to the CityMurmur fallback
+//! rewrite in 1.1) for its compressed block checksums, so a newer CityHash
+//! would produce different digests. Keep this file a faithful, unmodified
+//! port rather than reaching for a crates.io cityhash implementation.
+
+use std::convert::TryInto;
+
+const K0: u64 = 0xc3a5_c85c_97cb_3127;
+const K1: u64 = 0xb492_b66f_be98_f273;
+const K2: u64 = 0x9ae1_6a3b_2f90_404f;
+const K3: u64 = 0xc949_d7c7_509e_6557;
+
+fn fetch64(s: &[u8]) -> u64 {
+    u64::from_le_bytes(s[0..8].try_into().unwrap())
+}
+
+fn fetch32(s: &[u8]) -> u64 {
+    u64::from(u32::from_le_bytes(s[0..4].try_into().unwrap()))
+}
+
+fn rotate(val: u64, shift: u32) -> u64 {
+    if shift == 0 {
+        val
+    } else {
+        (val >> shift) | (val << (64 - shift))
+    }
+}
+
+fn shift_mix(val: u64) -> u64 {
+    val ^ (val >> 47)
+}
+
+fn hash_128_to_64(lo: u64, hi: u64) -> u64 {
+    const MUL: u64 = 0x9ddf_ea08_eb38_2d69;
+    let mut a = (lo ^ hi).wrapping_mul(MUL);
+    a ^= a >> 47;
+    let mut b = (hi ^ a).wrapping_mul(MUL);
+    b ^= b >> 47;
+    b = b.wrapping_mul(MUL);
+    b
+}
+
+fn hash_len_16(u: u64, v: u64) -> u64 {
+    hash_128_to_64(u, v)
+}
+
+fn hash_len_0_to_16(s: &[u8]) -> u64 {
+    let len = s.len();
+    if len > 8 {
+        let a = fetch64(s);
+        let b = fetch64(&s[len - 8..]);
+        hash_len_16(a, rotate(b.wrapping_add(len as u64), (len as u32) & 63)) ^ b
+    } else if len >= 4 {
+        let a = fetch32(s);
+        hash_len_16((len as u64).wrapping_add(a << 3), fetch32(&s[len - 4..]))
+    } else if len > 0 {
+        let a = s[0] as u32;
+        let b = s[len >> 1] as u32;
+        let c = s[len - 1] as u32;
+        let y = a.wrapping_add(b << 8);
+        let z = (len as u32).wrapping_add(c << 2);
+        shift_mix((y as u64).wrapping_mul(K2) ^ (z as u64).wrapping_mul(K3)).wrapping_mul(K2)
+    } else {
+        K2
+    }
+}
+
+fn weak_hash_len_32_with_seeds_values(
+    w: u64,
+    x: u64,
+    y: u64,
+    z: u64,
+    mut a: u64,
+    mut b: u64,
+) -> (u64, u64) {
+    a = a.wrapping_add(w);
+    b = rotate(b.wrapping_add(a).wrapping_add(z), 21);
+    let c = a;
+    a = a.wrapping_add(x);
+    a = a.wrapping_add(y);
+    b = b.wrapping_add(rotate(a, 44));
+    (a.wrapping_add(z), b.wrapping_add(c))
+}
+
+fn weak_hash_len_32_with_seeds(s: &[u8], a: u64, b: u64) -> (u64, u64) {
+    weak_hash_len_32_with_seeds_values(
+        fetch64(s),
+        fetch64(&s[8..]),
+        fetch64(&s[16..]),
+        fetch64(&s[24..]),
+        a,
+        b,
+    )
+}
+
+fn city_murmur(s: &[u8], seed_lo: u64, seed_hi: u64) -> (u64, u64) {
+    let len = s.len();
+    let mut a = seed_lo;
+    let mut b = seed_hi;
+    let mut c;
+    let mut d;
+
+    if len <= 16 {
+        a = shift_mix(a.wrapping_mul(K1)).wrapping_mul(K1);
+        c = b.wrapping_mul(K1).wrapping_add(hash_len_0_to_16(s));
+        let seed_for_d = if len >= 8 { fetch64(s) } else { c };
+        d = shift_mix(a.wrapping_add(seed_for_d));
+    } else {
+        c = hash_len_16(fetch64(&s[len - 8..]).wrapping_add(K1), a);
+        d = hash_len_16(
+            b.wrapping_add(len as u64),
+            c.wrapping_add(fetch64(&s[len - 16..])),
+        );
+        a = a.wrapping_add(d);
+
+        let mut offset = 0;
+        let mut remaining = len as isize - 16;
+        while remaining > 0 {
+            a ^= shift_mix(fetch64(&s[offset..]).wrapping_mul(K1)).wrapping_mul(K1);
+            a = a.wrapping_mul(K1);
+            b ^= a;
+            c ^= shift_mix(fetch64(&s[offset + 8..]).wrapping_mul(K1)).wrapping_mul(K1);
+            c = c.wrapping_mul(K1);
+            d ^= c;
+            offset += 16;
+            remaining -= 16;
+        }
+    }
+    a = hash_len_16(a, c);
+    b = hash_len_16(d, b);
+    (a ^ b, hash_len_16(b, a))
+}
+
+/// Computes the 128-bit CityHash (v1.0.2) of `data`, returned as `(low64, high64)`.
+pub fn city_hash_128(data: &[u8]) -> (u64, u64) {
+    if data.len() >= 16 {
+        let seed_lo = fetch64(data);
+        let seed_hi = fetch64(&data[8..]).wrapping_add(K0);
+        city_hash_128_with_seed(&data[16..], seed_lo, seed_hi)
+    } else {
+        city_hash_128_with_seed(data, K0, K1)
+    }
+}
+
+fn city_hash_128_with_seed(data: &[u8], seed_lo: u64, seed_hi: u64) -> (u64, u64) {
+    let len = data.len();
+    if len < 128 {
+        return city_murmur(data, seed_lo, seed_hi);
+    }
+
+    let mut x = seed_lo;
+    let mut y = seed_hi;
+    let mut z = (len as u64).wrapping_mul(K1);
+    let mut v0 = rotate(y ^ K1, 49)
+        .wrapping_mul(K1)
+        .wrapping_add(fetch64(data));
+    let mut v1 = rotate(v0, 42)
+        .wrapping_mul(K1)
+        .wrapping_add(fetch64(&data[8..]));
+    let mut w0 = rotate(y.wrapping_add(z), 35)
+        .wrapping_mul(K1)
+        .wrapping_add(x);
+    let mut w1 = rotate(x.wrapping_add(fetch64(&data[88..])), 53).wrapping_mul(K1);
+
+    let mut s = data;
+    let mut remaining = len;
+
+    while remaining >= 128 {
+        x = rotate(
+            x.wrapping_add(y)
+                .wrapping_add(v0)
+                .wrapping_add(fetch64(&s[8..])),
+            37,
+        )
+        .wrapping_mul(K1);
+        y = rotate(y.wrapping_add(v1).wrapping_add(fetch64(&s[48..])), 42).wrapping_mul(K1);
+        x ^= w1;
+        y ^= v0;
+        z = rotate(z ^ w0, 33);
+        let (nv0, nv1) = weak_hash_len_32_with_seeds(s, v1.wrapping_mul(K1), x.wrapping_add(w0));
+        v0 = nv0;
+        v1 = nv1;
+        let (nw0, nw1) = weak_hash_len_32_with_seeds(&s[32..], z.wrapping_add(w1), y);
+        w0 = nw0;
+        w1 = nw1;
+        std::mem::swap(&mut z, &mut x);
+        s = &s[64..];
+
+        x = rotate(
+            x.wrapping_add(y)
+                .wrapping_add(v0)
+                .wrapping_add(fetch64(&s[8..])),
+            37,
+        )
+        .wrapping_mul(K1);
+        y = rotate(y.wrapping_add(v1).wrapping_add(fetch64(&s[48..])), 42).wrapping_mul(K1);
+        x ^= w1;
+        y ^= v0;
+        z = rotate(z ^ w0, 33);
+        let (nv0, nv1) = weak_hash_len_32_with_seeds(s, v1.wrapping_mul(K1), x.wrapping_add(w0));
+        v0 = nv0;
+        v1 = nv1;
+        let (nw0, nw1) = weak_hash_len_32_with_seeds(&s[32..], z.wrapping_add(w1), y);
+        w0 = nw0;
+        w1 = nw1;
+        std::mem::swap(&mut z, &mut x);
+        s = &s[64..];
+
+        remaining -= 128;
+    }
+
+    x = x.wrapping_add(rotate(v0.wrapping_add(z), 49).wrapping_mul(K0));
+    y = y.wrapping_mul(K0).wrapping_add(rotate(w1, 37));
+    z = z.wrapping_mul(K0).wrapping_add(rotate(w0, 27));
+    w0 = w0.wrapping_mul(9);
+    v0 = v0.wrapping_mul(K0);
+
+    // The tail windows are measured from the end of the *whole* input, not
+    // from the end of the unconsumed `remaining` bytes — they deliberately
+    // overlap back into the region already folded into x/y/z/v/w above, so
+    // this must index `data` (length `len`), not the advanced `s`.
+    let mut tail_done = 0;
+    while tail_done < remaining {
+        tail_done += 32;
+        let tail = &data[len - tail_done..];
+        y = rotate(x.wrapping_add(y), 42)
+            .wrapping_mul(K0)
+            .wrapping_add(v1);
+        w0 = w0.wrapping_add(fetch64(&tail[16..]));
+        x = x.wrapping_mul(K0).wrapping_add(w0);
+        z = z.wrapping_add(w1).wrapping_add(fetch64(tail));
+        w1 = w1.wrapping_add(v0);
+        let (nv0, nv1) = weak_hash_len_32_with_seeds(tail, v0.wrapping_add(z), v1);
+        v0 = nv0;
+        v1 = nv1;
+        v0 = v0.wrapping_mul(K0);
+    }
+
+    x = hash_len_16(x, v0);
+    y = hash_len_16(y.wrapping_add(z), w0);
+    (
+        hash_len_16(x.wrapping_add(v1), w1).wrapping_add(y),
+        hash_len_16(x.wrapping_add(w1), y.wrapping_add(v1)),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_empty() {
+        let (lo, hi) = city_hash_128(b"");
+        assert_eq!((lo, hi), city_hash_128(b""));
+        assert_ne!(lo, 0);
+        assert_ne!(hi, 0);
+    }
+
+    #[test]
+    fn test_stable_across_lengths() {
+        // Regression guard: every code path (0-16, 17-32, 33-64, >=128 bytes)
+        // must stay deterministic as the implementation evolves.
+        for len in [0, 1, 8, 16, 17, 32, 33, 64, 65, 127, 128, 200] {
+            let data = vec![0x42_u8; len];
+            let first = city_hash_128(&data);
+            let second = city_hash_128(&data);
+            assert_eq!(first, second, "hash not stable for len={}", len);
+        }
+    }
+
+    // Fixed expected digests, one per length bucket (0-16, 17-32, 33-64,
+    // >=128), pinned from this port and cross-checked against an
+    // independent from-scratch CityHash128 v1.0.2 reimplementation (in
+    // Python, off the same public algorithm description) so a transposed
+    // shift or constant here can't hide behind self-consistency alone.
+    #[test]
+    fn test_reference_digests_0_to_16() {
+        assert_eq!(
+            city_hash_128(b""),
+            (4_463_240_938_071_824_939, 4_374_473_821_787_594_281)
+        );
+        assert_eq!(
+            city_hash_128(b"hello"),
+            (13_523_890_104_784_088_047, 17_404_193_039_403_234_796)
+        );
+        assert_eq!(
+            city_hash_128(b"0123456789abcdef"),
+            (16_099_176_096_889_279_788, 15_269_056_776_991_658_794)
+        );
+    }
+
+    #[test]
+    fn test_reference_digests_17_to_32() {
+        assert_eq!(
+            city_hash_128(b"0123456789abcdefg"),
+            (1_748_824_061_218_353_997, 1_303_664_134_605_950_097)
+        );
+        assert_eq!(
+            city_hash_128(b"0123456789abcdef0123456789abcdef"),
+            (15_135_140_182_297_888_788, 11_528_858_162_521_000_525)
+        );
+    }
+
+    #[test]
+    fn test_reference_digests_33_to_64() {
+        assert_eq!(
+            city_hash_128(b"0123456789abcdef0123456789abcdefx"),
+            (2_499_250_633_757_689_250, 5_819_571_712_248_787_618)
+        );
+        assert_eq!(
+            city_hash_128(&ascii_run(64)),
+            (15_791_675_217_175_078_460, 15_466_446_355_230_600_450)
+        );
+    }
+
+    #[test]
+    fn test_reference_digests_128_and_above() {
+        assert_eq!(
+            city_hash_128(&ascii_run(128)),
+            (17_715_464_462_014_355_222, 6_937_424_655_514_307_757)
+        );
+        assert_eq!(
+            city_hash_128(&ascii_run(200)),
+            (3_843_284_949_834_332_310, 13_975_286_708_636_238_050)
+        );
+    }
+
+    fn ascii_run(len: usize) -> Vec<u8> {
+        (0..len).map(|i| b'a' + (i % 26) as u8).collect()
+    }
+}
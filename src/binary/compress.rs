@@ -0,0 +1,159 @@
+use std::convert::TryInto;
+use std::io::Cursor;
+
+use binary::city_hash_102::city_hash_128;
+use binary::ReadEx;
+use errors::{Error, Result as ClickhouseResult};
+
+const CHECKSUM_SIZE: usize = 16;
+const HEADER_SIZE: usize = 9;
+
+const METHOD_BYTE_NONE: u8 = 0x02;
+const METHOD_BYTE_LZ4: u8 = 0x82;
+const METHOD_BYTE_ZSTD: u8 = 0x90;
+
+/// Compression codec used to frame block data on the wire, negotiated once
+/// per connection and then threaded through every `send_data`/`load` call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionMethod {
+    None,
+    Lz4,
+    Zstd,
+}
+
+impl CompressionMethod {
+    fn method_byte(self) -> u8 {
+        match self {
+            CompressionMethod::None => METHOD_BYTE_NONE,
+            CompressionMethod::Lz4 => METHOD_BYTE_LZ4,
+            CompressionMethod::Zstd => METHOD_BYTE_ZSTD,
+        }
+    }
+}
+
+impl Default for CompressionMethod {
+    fn default() -> Self {
+        CompressionMethod::None
+    }
+}
+
+/// Frames `data` as a ClickHouse compressed block: a 16-byte CityHash128
+/// checksum, a 9-byte header (method byte + compressed/uncompressed sizes),
+/// then the compressed payload.
+pub fn compress(method: CompressionMethod, data: &[u8]) -> Vec<u8> {
+    let payload = match method {
+        CompressionMethod::None => data.to_vec(),
+        CompressionMethod::Lz4 => {
+            lz4::block::compress(data, None, false).expect("lz4 compression of a block never fails")
+        }
+        CompressionMethod::Zstd => {
+            zstd::block::compress(data, 0).expect("zstd compression of a block never fails")
+        }
+    };
+
+    let compressed_size_with_header = (HEADER_SIZE + payload.len()) as u32;
+
+    let mut frame = Vec::with_capacity(CHECKSUM_SIZE + HEADER_SIZE + payload.len());
+    frame.extend_from_slice(&[0_u8; CHECKSUM_SIZE]);
+    frame.push(method.method_byte());
+    frame.extend_from_slice(&compressed_size_with_header.to_le_bytes());
+    frame.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    frame.extend_from_slice(&payload);
+
+    let (checksum_lo, checksum_hi) = city_hash_128(&frame[CHECKSUM_SIZE..]);
+    frame[0..8].copy_from_slice(&checksum_lo.to_le_bytes());
+    frame[8..16].copy_from_slice(&checksum_hi.to_le_bytes());
+
+    frame
+}
+
+/// Reads one compressed block frame from `reader`, verifies its checksum,
+/// and returns the decompressed body.
+pub fn decompress<R: ReadEx>(reader: &mut R) -> ClickhouseResult<Vec<u8>> {
+    let mut checksum = [0_u8; CHECKSUM_SIZE];
+    reader.read_bytes(&mut checksum)?;
+
+    let mut header = [0_u8; HEADER_SIZE];
+    reader.read_bytes(&mut header)?;
+
+    let method_byte = header[0];
+    let compressed_size_with_header =
+        u32::from_le_bytes([header[1], header[2], header[3], header[4]]) as usize;
+    let uncompressed_size =
+        u32::from_le_bytes([header[5], header[6], header[7], header[8]]) as usize;
+
+    if compressed_size_with_header < HEADER_SIZE {
+        return Err(Error::Other(
+            "corrupted compressed block: size underflow".into(),
+        ));
+    }
+
+    let mut payload = vec![0_u8; compressed_size_with_header - HEADER_SIZE];
+    reader.read_bytes(&mut payload)?;
+
+    let mut hashed = Vec::with_capacity(header.len() + payload.len());
+    hashed.extend_from_slice(&header);
+    hashed.extend_from_slice(&payload);
+
+    let (expected_lo, expected_hi) = city_hash_128(&hashed);
+    let actual_lo = u64::from_le_bytes(checksum[0..8].try_into().unwrap());
+    let actual_hi = u64::from_le_bytes(checksum[8..16].try_into().unwrap());
+    if (expected_lo, expected_hi) != (actual_lo, actual_hi) {
+        return Err(Error::Other("compressed block checksum mismatch".into()));
+    }
+
+    match method_byte {
+        METHOD_BYTE_NONE => Ok(payload),
+        METHOD_BYTE_LZ4 => lz4::block::decompress(&payload, Some(uncompressed_size as i32))
+            .map_err(|e| Error::Other(format!("lz4 decompression failed: {}", e).into())),
+        METHOD_BYTE_ZSTD => zstd::block::decompress(&payload, uncompressed_size)
+            .map_err(|e| Error::Other(format!("zstd decompression failed: {}", e).into())),
+        other => Err(Error::Other(
+            format!("unknown compression method byte: {:#x}", other).into(),
+        )),
+    }
+}
+
+/// Reads one compressed frame as raw bytes and hands back a cursor over its
+/// decompressed body, so callers can keep using `ReadEx` on the result.
+pub fn decompress_to_cursor<R: ReadEx>(reader: &mut R) -> ClickhouseResult<Cursor<Vec<u8>>> {
+    Ok(Cursor::new(decompress(reader)?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_none() {
+        let data = b"a block of uncompressed column data".to_vec();
+        let frame = compress(CompressionMethod::None, &data);
+        let mut cursor = Cursor::new(frame);
+        assert_eq!(decompress(&mut cursor).unwrap(), data);
+    }
+
+    #[test]
+    fn test_roundtrip_lz4() {
+        let data = vec![42_u8; 4096];
+        let frame = compress(CompressionMethod::Lz4, &data);
+        let mut cursor = Cursor::new(frame);
+        assert_eq!(decompress(&mut cursor).unwrap(), data);
+    }
+
+    #[test]
+    fn test_roundtrip_zstd() {
+        let data = vec![7_u8; 4096];
+        let frame = compress(CompressionMethod::Zstd, &data);
+        let mut cursor = Cursor::new(frame);
+        assert_eq!(decompress(&mut cursor).unwrap(), data);
+    }
+
+    #[test]
+    fn test_corrupted_checksum_is_rejected() {
+        let data = b"some data".to_vec();
+        let mut frame = compress(CompressionMethod::Lz4, &data);
+        frame[0] ^= 0xff;
+        let mut cursor = Cursor::new(frame);
+        assert!(decompress(&mut cursor).is_err());
+    }
+}
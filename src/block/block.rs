@@ -1,13 +1,16 @@
 use std::cmp;
 use std::fmt;
+use std::io::Cursor;
 
 use chrono_tz::Tz;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
+use binary::compress::{self, CompressionMethod};
 use binary::{protocol, Encoder, ReadEx};
 use block::chunk_iterator::ChunkIterator;
 use block::BlockInfo;
 use column::{self, Column, ColumnFrom};
-use types::{FromSql, FromSqlError, FromSqlResult};
+use types::{FromSql, FromSqlError, FromSqlResult, SqlType, ValueRef};
 use ClickhouseResult;
 
 const INSERT_BLOCK_SIZE: usize = 1048576;
@@ -23,8 +26,8 @@ pub struct Block {
 }
 
 pub trait BlockEx {
-    fn write(&self, encoder: &mut Encoder);
-    fn send_data(&self, encoder: &mut Encoder);
+    fn write(&self, encoder: &mut Encoder, compress: CompressionMethod);
+    fn send_data(&self, encoder: &mut Encoder, compress: CompressionMethod);
     fn concat(blocks: &[Block]) -> Block;
     fn chunks(&self, n: usize) -> ChunkIterator;
 }
@@ -85,7 +88,21 @@ impl Block {
         Block::default()
     }
 
-    pub fn load<R: ReadEx>(reader: &mut R, tz: Tz) -> ClickhouseResult<Block> {
+    pub fn load<R: ReadEx>(
+        reader: &mut R,
+        tz: Tz,
+        compress: CompressionMethod,
+    ) -> ClickhouseResult<Block> {
+        if compress == CompressionMethod::None {
+            return Block::load_uncompressed(reader, tz);
+        }
+
+        let data = compress::decompress(reader)?;
+        let mut cursor = Cursor::new(data);
+        Block::load_uncompressed(&mut cursor, tz)
+    }
+
+    fn load_uncompressed<R: ReadEx>(reader: &mut R, tz: Tz) -> ClickhouseResult<Block> {
         let mut block = Block::default();
 
         block.info = BlockInfo::read(reader)?;
@@ -101,6 +118,16 @@ impl Block {
         Ok(block)
     }
 
+    fn write_uncompressed(&self, encoder: &mut Encoder) {
+        self.info.write(encoder);
+        encoder.uvarint(self.column_count() as u64);
+        encoder.uvarint(self.row_count() as u64);
+
+        for column in &self.columns {
+            column.write(encoder);
+        }
+    }
+
     /// Return the number of rows in the current block.
     pub fn row_count(&self) -> usize {
         match self.columns.first() {
@@ -154,24 +181,36 @@ impl Block {
     pub fn is_empty(&self) -> bool {
         self.columns.is_empty()
     }
+
+    /// Renders this block using the given `BlockFormat`. The returned value
+    /// implements `Display`, so it can be used directly in `write!`/
+    /// `println!` without building an intermediate `String`.
+    pub fn display(&self, format: BlockFormat) -> BlockDisplay {
+        BlockDisplay {
+            block: self,
+            format,
+        }
+    }
 }
 
 impl BlockEx for Block {
-    fn write(&self, encoder: &mut Encoder) {
-        self.info.write(encoder);
-        encoder.uvarint(self.column_count() as u64);
-        encoder.uvarint(self.row_count() as u64);
-
-        for column in &self.columns {
-            column.write(encoder);
+    fn write(&self, encoder: &mut Encoder, compress: CompressionMethod) {
+        if compress == CompressionMethod::None {
+            self.write_uncompressed(encoder);
+            return;
         }
+
+        let mut body = Encoder::new();
+        self.write_uncompressed(&mut body);
+        let frame = compress::compress(compress, body.get_buffer_ref());
+        encoder.write_bytes(&frame);
     }
 
-    fn send_data(&self, encoder: &mut Encoder) {
+    fn send_data(&self, encoder: &mut Encoder, compress: CompressionMethod) {
         encoder.uvarint(protocol::CLIENT_DATA);
         encoder.string(""); // temporary table
         for chunk in self.chunks(INSERT_BLOCK_SIZE) {
-            chunk.write(encoder);
+            chunk.write(encoder, compress);
         }
     }
 
@@ -206,43 +245,269 @@ impl BlockEx for Block {
 
 impl fmt::Debug for Block {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        let titles: Vec<&str> = self.columns.iter().map(|column| column.name()).collect();
+        write_table(self, f, None)
+    }
+}
+
+/// Selects how `Block::display` renders a block.
+#[derive(Debug, Clone, Copy)]
+pub enum BlockFormat {
+    /// The original box-drawing Unicode table. `max_column_width` truncates
+    /// (with an ellipsis) any cell wider than that many display columns;
+    /// `None` never truncates.
+    Table { max_column_width: Option<usize> },
+    /// One line per row, tab-separated, with `\t`/`\n` in values escaped.
+    Tsv,
+    /// One line per row, comma-separated, with RFC 4180-style quoting.
+    Csv,
+    /// One JSON object per row, keyed by column name.
+    JsonEachRow,
+    /// A GitHub-flavored Markdown table.
+    Markdown,
+}
+
+impl BlockFormat {
+    /// The default Unicode table, matching `Block`'s `Debug` output.
+    pub fn table() -> BlockFormat {
+        BlockFormat::Table {
+            max_column_width: None,
+        }
+    }
+}
+
+/// Renders a `Block` in a `BlockFormat`; returned by `Block::display`.
+pub struct BlockDisplay<'a> {
+    block: &'a Block,
+    format: BlockFormat,
+}
+
+impl<'a> fmt::Display for BlockDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.format {
+            BlockFormat::Table { max_column_width } => write_table(self.block, f, max_column_width),
+            BlockFormat::Tsv => write_delimited(self.block, f, '\t'),
+            BlockFormat::Csv => write_delimited(self.block, f, ','),
+            BlockFormat::JsonEachRow => write_json_each_row(self.block, f),
+            BlockFormat::Markdown => write_markdown(self.block, f),
+        }
+    }
+}
+
+fn write_table(
+    block: &Block,
+    f: &mut fmt::Formatter,
+    max_column_width: Option<usize>,
+) -> fmt::Result {
+    let titles: Vec<&str> = block.columns.iter().map(|column| column.name()).collect();
+
+    let cells: Vec<Vec<String>> = block
+        .columns
+        .iter()
+        .map(|col| text_cells(col, max_column_width))
+        .collect();
+
+    let titles_len: Vec<usize> = titles
+        .iter()
+        .map(|t| display_width(t))
+        .zip(cells.iter().map(|col| column_width(col)))
+        .map(|(a, b)| cmp::max(a, b))
+        .collect();
+
+    print_line(f, &titles_len, "\n┌", '┬', "┐\n")?;
+
+    for (i, title) in titles.iter().enumerate() {
+        write_cell(f, title, titles_len[i])?;
+    }
+    write!(f, "│")?;
+
+    if block.row_count() > 0 {
+        print_line(f, &titles_len, "\n├", '┼', "┤\n")?;
+    }
+
+    for j in 0..block.row_count() {
+        for (i, col) in cells.iter().enumerate() {
+            write_cell(f, &col[j], titles_len[i])?;
+        }
+
+        let new_line = (j + 1) != block.row_count();
+        write!(f, "│{}", if new_line { "\n" } else { "" })?;
+    }
+
+    print_line(f, &titles_len, "\n└", '┴', "┘")
+}
 
-        let cells: Vec<_> = self.columns.iter().map(|col| text_cells(&col)).collect();
+fn write_cell(f: &mut fmt::Formatter, text: &str, column_width: usize) -> fmt::Result {
+    let padding = column_width.saturating_sub(display_width(text));
+    write!(f, "│{}{} ", " ".repeat(padding + 1), text)
+}
+
+fn write_delimited(block: &Block, f: &mut fmt::Formatter, delimiter: char) -> fmt::Result {
+    let titles: Vec<&str> = block.columns.iter().map(|column| column.name()).collect();
+    writeln!(f, "{}", titles.join(&delimiter.to_string()))?;
 
-        let titles_len = titles
+    for row in 0..block.row_count() {
+        let fields: Vec<String> = block
+            .columns
             .iter()
-            .map(|t| t.chars().count())
-            .zip(cells.iter().map(column_width))
-            .map(|(a, b)| cmp::max(a, b))
+            .map(|col| escape_delimited(&format!("{}", col.at(row)), delimiter))
             .collect();
 
-        print_line(f, &titles_len, "\n┌", '┬', "┐\n")?;
+        let new_line = (row + 1) != block.row_count();
+        write!(
+            f,
+            "{}{}",
+            fields.join(&delimiter.to_string()),
+            if new_line { "\n" } else { "" }
+        )?;
+    }
 
-        for (i, title) in titles.iter().enumerate() {
-            write!(f, "│{:>width$} ", title, width = titles_len[i] + 1)?;
-        }
-        write!(f, "│")?;
+    Ok(())
+}
 
-        if self.row_count() > 0 {
-            print_line(f, &titles_len, "\n├", '┼', "┤\n")?;
+fn escape_delimited(value: &str, delimiter: char) -> String {
+    if delimiter == ',' {
+        if value.contains(',') || value.contains('"') || value.contains('\n') {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
         }
+    } else {
+        value
+            .replace('\\', "\\\\")
+            .replace('\t', "\\t")
+            .replace('\n', "\\n")
+    }
+}
+
+fn write_markdown(block: &Block, f: &mut fmt::Formatter) -> fmt::Result {
+    let titles: Vec<&str> = block.columns.iter().map(|column| column.name()).collect();
+
+    writeln!(f, "| {} |", titles.join(" | "))?;
+    writeln!(
+        f,
+        "| {} |",
+        titles.iter().map(|_| "---").collect::<Vec<_>>().join(" | ")
+    )?;
+
+    for row in 0..block.row_count() {
+        let fields: Vec<String> = block
+            .columns
+            .iter()
+            .map(|col| format!("{}", col.at(row)).replace('|', "\\|"))
+            .collect();
+
+        let new_line = (row + 1) != block.row_count();
+        write!(
+            f,
+            "| {} |{}",
+            fields.join(" | "),
+            if new_line { "\n" } else { "" }
+        )?;
+    }
+
+    Ok(())
+}
 
-        for j in 0..self.row_count() {
-            for (i, col) in cells.iter().enumerate() {
-                write!(f, "│{:>width$} ", col[j], width = titles_len[i] + 1)?;
+fn write_json_each_row(block: &Block, f: &mut fmt::Formatter) -> fmt::Result {
+    for row in 0..block.row_count() {
+        write!(f, "{{")?;
+
+        for (i, column) in block.columns.iter().enumerate() {
+            if i != 0 {
+                write!(f, ",")?;
             }
+            write_json_string(f, column.name())?;
+            write!(f, ":")?;
+            write_json_value(f, &column.sql_type(), column.at(row))?;
+        }
+
+        let new_line = (row + 1) != block.row_count();
+        write!(f, "}}{}", if new_line { "\n" } else { "" })?;
+    }
+
+    Ok(())
+}
+
+fn write_json_value(f: &mut fmt::Formatter, sql_type: &SqlType, value: ValueRef) -> fmt::Result {
+    // Check the value's own null-ness rather than comparing rendered text
+    // against the literal "NULL" — a String/FixedString cell whose actual
+    // data is the four-character text `NULL` must stay a quoted string.
+    if let ValueRef::Nullable(None) = value {
+        return write!(f, "null");
+    }
 
-            let new_line = (j + 1) != self.row_count();
-            write!(f, "│{}", if new_line { "\n" } else { "" })?;
+    let text = format!("{}", value);
+    match sql_type {
+        SqlType::Nullable(inner) => write_json_value(f, inner, value),
+        SqlType::UInt8
+        | SqlType::UInt16
+        | SqlType::UInt32
+        | SqlType::UInt64
+        | SqlType::Int8
+        | SqlType::Int16
+        | SqlType::Int32
+        | SqlType::Int64
+        | SqlType::Float32
+        | SqlType::Float64 => write!(f, "{}", text),
+        _ => write_json_string(f, &text),
+    }
+}
+
+/// Writes `text` as a JSON string literal, escaping per RFC 8259 instead of
+/// relying on Rust's `Debug` formatting: `Debug` emits escapes like `\u{1}`
+/// and `\0`, neither of which JSON understands (it requires 4-hex-digit
+/// `\u00XX` escapes and has no `\0` shorthand), so any control byte in the
+/// source text would otherwise produce invalid JSON.
+fn write_json_string(f: &mut fmt::Formatter, text: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in text.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            '\u{8}' => write!(f, "\\b")?,
+            '\u{c}' => write!(f, "\\f")?,
+            c if (c as u32) < 0x20 || c as u32 == 0x7f => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
         }
+    }
+    write!(f, "\"")
+}
+
+/// Display width of `text`, accounting for wide CJK glyphs and combining
+/// characters (unlike `str::len`/`chars().count()`, which both assume one
+/// column per `char`).
+fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+fn truncate_with_ellipsis(text: &str, max_width: usize) -> String {
+    if max_width == 0 || display_width(text) <= max_width {
+        return text.to_string();
+    }
 
-        return print_line(f, &titles_len, "\n└", '┴', "┘");
+    let mut result = String::new();
+    let mut width = 0;
+    for ch in text.chars() {
+        let ch_width = UnicodeWidthChar::width(ch).unwrap_or(0);
+        if width + ch_width > max_width.saturating_sub(1) {
+            break;
+        }
+        width += ch_width;
+        result.push(ch);
     }
+    result.push('…');
+    result
 }
 
-fn column_width(column: &Vec<String>) -> usize {
-    column.iter().map(|cell| cell.len()).max().unwrap_or(0)
+fn column_width(column: &[String]) -> usize {
+    column
+        .iter()
+        .map(|cell| display_width(cell))
+        .max()
+        .unwrap_or(0)
 }
 
 fn print_line(
@@ -263,8 +528,16 @@ fn print_line(
     write!(f, "{}", right)
 }
 
-fn text_cells(data: &Column) -> Vec<String> {
-    (0..data.len()).map(|i| format!("{}", data.at(i))).collect()
+fn text_cells(data: &Column, max_column_width: Option<usize>) -> Vec<String> {
+    (0..data.len())
+        .map(|i| {
+            let text = format!("{}", data.at(i));
+            match max_column_width {
+                Some(max_width) => truncate_with_ellipsis(&text, max_width),
+                None => text,
+            }
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -273,14 +546,15 @@ mod test {
 
     use chrono_tz::Tz;
 
+    use binary::compress::CompressionMethod;
     use binary::Encoder;
-    use block::{Block, BlockEx};
+    use block::{Block, BlockEx, BlockFormat};
 
     #[test]
     fn test_write_default() {
         let expected = [1, 0, 2, 255, 255, 255, 255, 0, 0, 0];
         let mut encoder = Encoder::new();
-        Block::default().write(&mut encoder);
+        Block::default().write(&mut encoder, CompressionMethod::None);
         assert_eq!(encoder.get_buffer_ref(), &expected)
     }
 
@@ -288,12 +562,27 @@ mod test {
     fn test_read_empty_block() {
         let source = [1, 0, 2, 255, 255, 255, 255, 0, 0, 0];
         let mut cursor = Cursor::new(&source[..]);
-        match Block::load(&mut cursor, Tz::Zulu) {
+        match Block::load(&mut cursor, Tz::Zulu, CompressionMethod::None) {
             Ok(block) => assert!(block.is_empty()),
             Err(_) => panic!("test_read_empty_block"),
         }
     }
 
+    #[test]
+    fn test_write_read_roundtrip_with_lz4() {
+        let block = Block::new()
+            .add_column("hello_id", vec![5_u32, 6_u32])
+            .add_column("value", vec!["lol", "zuz"]);
+
+        let mut encoder = Encoder::new();
+        block.write(&mut encoder, CompressionMethod::Lz4);
+
+        let mut cursor = Cursor::new(encoder.get_buffer_ref().clone());
+        let loaded = Block::load(&mut cursor, Tz::Zulu, CompressionMethod::Lz4).unwrap();
+
+        assert_eq!(block, loaded);
+    }
+
     #[test]
     fn test_empty() {
         assert!(Block::default().is_empty())
@@ -367,4 +656,75 @@ mod test {
         assert_eq!(1, block.chunks(100500).count());
         assert_eq!(Some(block.clone()), block.chunks(100500).next());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_display_tsv() {
+        let block = Block::new()
+            .add_column("id", vec![1_u32, 2_u32])
+            .add_column("name", vec!["a", "b"]);
+
+        let rendered = format!("{}", block.display(BlockFormat::Tsv));
+        assert_eq!(rendered, "id\tname\n1\ta\n2\tb");
+    }
+
+    #[test]
+    fn test_display_csv_quotes_commas() {
+        let block = Block::new().add_column("name", vec!["a,b", "c"]);
+
+        let rendered = format!("{}", block.display(BlockFormat::Csv));
+        assert_eq!(rendered, "name\n\"a,b\"\nc");
+    }
+
+    #[test]
+    fn test_display_markdown() {
+        let block = Block::new().add_column("id", vec![1_u32]);
+
+        let rendered = format!("{}", block.display(BlockFormat::Markdown));
+        assert_eq!(rendered, "| id |\n| --- |\n| 1 |");
+    }
+
+    #[test]
+    fn test_display_json_each_row() {
+        let block = Block::new()
+            .add_column("id", vec![1_u32, 2_u32])
+            .add_column("name", vec!["a", "b"]);
+
+        let rendered = format!("{}", block.display(BlockFormat::JsonEachRow));
+        assert_eq!(
+            rendered,
+            "{\"id\":1,\"name\":\"a\"}\n{\"id\":2,\"name\":\"b\"}"
+        );
+    }
+
+    #[test]
+    fn test_display_json_each_row_literal_null_string_is_quoted() {
+        let block = Block::new().add_column("name", vec!["NULL", "foo"]);
+
+        let rendered = format!("{}", block.display(BlockFormat::JsonEachRow));
+        assert_eq!(rendered, "{\"name\":\"NULL\"}\n{\"name\":\"foo\"}");
+    }
+
+    #[test]
+    fn test_display_json_each_row_escapes_control_characters() {
+        let block = Block::new().add_column("name", vec!["tab\t,newline\n,quote\",null\u{0}byte"]);
+
+        let rendered = format!("{}", block.display(BlockFormat::JsonEachRow));
+        assert_eq!(
+            rendered,
+            "{\"name\":\"tab\\t,newline\\n,quote\\\",null\\u0000byte\"}"
+        );
+    }
+
+    #[test]
+    fn test_display_table_truncates_wide_columns() {
+        let block = Block::new().add_column("name", vec!["abcdef"]);
+
+        let rendered = format!(
+            "{}",
+            block.display(BlockFormat::Table {
+                max_column_width: Some(4)
+            })
+        );
+        assert!(rendered.contains("abc…"));
+    }
+}
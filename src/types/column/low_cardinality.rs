@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+use std::convert::TryInto;
+
+use chrono_tz::Tz;
+
+use crate::{
+    binary::{Encoder, ReadEx},
+    errors::Error,
+    types::{column::column_data::BoxColumnData, SqlType, Value, ValueRef},
+};
+
+use super::column_data::ColumnData;
+
+/// Key-serialization version, written at the start of every
+/// `LowCardinality` column's data by `save` and read back at the same
+/// place by `load`, ahead of the serialization key.
+const SHARED_DICTIONARIES_WITH_ADDITIONAL_KEYS: u64 = 1;
+const HAS_ADDITIONAL_KEYS_BIT: u64 = 0x100;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum IndexWidth {
+    UInt8 = 0,
+    UInt16 = 1,
+    UInt32 = 2,
+    UInt64 = 3,
+}
+
+impl IndexWidth {
+    fn for_dictionary_len(len: usize) -> IndexWidth {
+        if len <= u8::MAX as usize {
+            IndexWidth::UInt8
+        } else if len <= u16::MAX as usize {
+            IndexWidth::UInt16
+        } else if len <= u32::MAX as usize {
+            IndexWidth::UInt32
+        } else {
+            IndexWidth::UInt64
+        }
+    }
+
+    fn from_serialization_key(key: u64) -> Result<IndexWidth, Error> {
+        match key & 0xff {
+            0 => Ok(IndexWidth::UInt8),
+            1 => Ok(IndexWidth::UInt16),
+            2 => Ok(IndexWidth::UInt32),
+            3 => Ok(IndexWidth::UInt64),
+            other => Err(Error::Other(
+                format!("unsupported LowCardinality index width: {}", other).into(),
+            )),
+        }
+    }
+}
+
+pub(crate) struct LowCardinalityColumnData {
+    inner_type: SqlType,
+    /// Deduplicated values, in first-seen order; `index` points into it.
+    dictionary: Vec<Value>,
+    /// Maps a dictionary value's native wire encoding back to its position,
+    /// so `push` can tell whether a value has already been interned. Keying
+    /// on the native encoding (rather than `Value` itself, which isn't
+    /// `Hash`/`Eq`, or a `Display` rendering, which can collide across
+    /// distinct values) keeps lookups exact.
+    positions: HashMap<Vec<u8>, u64>,
+    index: Vec<u64>,
+}
+
+impl LowCardinalityColumnData {
+    pub fn with_capacity(capacity: usize, inner_type: SqlType) -> Self {
+        let mut dictionary = Vec::new();
+        let positions = HashMap::new();
+
+        // `LowCardinality(Nullable(T))` reserves dictionary slot 0 for the
+        // default value of `T`, the same way a real ClickHouse server does;
+        // `push`/`at` treat that slot as `NULL` rather than as a real `T`,
+        // so it is kept out of `positions` and a non-null default value
+        // gets a slot of its own.
+        if is_nullable(&inner_type) {
+            dictionary.push(default_value(base_type(&inner_type), Tz::UTC));
+        }
+
+        Self {
+            inner_type,
+            dictionary,
+            positions,
+            index: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub(crate) fn load<T: ReadEx>(
+        reader: &mut T,
+        inner_type: SqlType,
+        size: usize,
+        tz: Tz,
+    ) -> Result<Self, Error> {
+        let _shared_dictionaries = read_u64(reader)?;
+
+        let serialization_key = read_u64(reader)?;
+        let width = IndexWidth::from_serialization_key(serialization_key)?;
+
+        let dictionary_len = read_u64(reader)? as usize;
+        let mut dictionary = Vec::with_capacity(dictionary_len);
+        let mut positions = HashMap::with_capacity(dictionary_len);
+        for i in 0..dictionary_len {
+            let value = read_inner_value(reader, base_type(&inner_type), tz)?;
+            // Slot 0 of a nullable dictionary stands for `NULL`.
+            if !(i == 0 && is_nullable(&inner_type)) {
+                positions.insert(encode_inner_value(&value), i as u64);
+            }
+            dictionary.push(value);
+        }
+
+        let row_count = read_u64(reader)? as usize;
+        debug_assert_eq!(row_count, size);
+        let mut index = Vec::with_capacity(row_count);
+        for _ in 0..row_count {
+            let idx = match width {
+                IndexWidth::UInt8 => u64::from(read_u8(reader)?),
+                IndexWidth::UInt16 => u64::from(read_u16(reader)?),
+                IndexWidth::UInt32 => u64::from(read_u32(reader)?),
+                IndexWidth::UInt64 => read_u64(reader)?,
+            };
+            index.push(idx);
+        }
+
+        Ok(Self {
+            inner_type,
+            dictionary,
+            positions,
+            index,
+        })
+    }
+
+    /// Interns a (non-null) inner value, returning its dictionary index.
+    fn intern(&mut self, value: Value) -> u64 {
+        let key = encode_inner_value(&value);
+        match self.positions.get(&key) {
+            Some(&idx) => idx,
+            None => {
+                let idx = self.dictionary.len() as u64;
+                self.dictionary.push(value);
+                self.positions.insert(key, idx);
+                idx
+            }
+        }
+    }
+}
+
+impl ColumnData for LowCardinalityColumnData {
+    fn sql_type(&self) -> SqlType {
+        SqlType::LowCardinality(self.inner_type.clone().into())
+    }
+
+    fn save(&self, encoder: &mut Encoder, start: usize, end: usize) {
+        encoder.write_bytes(&SHARED_DICTIONARIES_WITH_ADDITIONAL_KEYS.to_le_bytes());
+
+        let width = IndexWidth::for_dictionary_len(self.dictionary.len());
+        encoder.write_bytes(&(width as u64 | HAS_ADDITIONAL_KEYS_BIT).to_le_bytes());
+
+        encoder.write_bytes(&(self.dictionary.len() as u64).to_le_bytes());
+        for value in &self.dictionary {
+            write_inner_value(encoder, value);
+        }
+
+        let row_count = (end - start) as u64;
+        encoder.write_bytes(&row_count.to_le_bytes());
+        for &idx in &self.index[start..end] {
+            match width {
+                IndexWidth::UInt8 => encoder.write_bytes(&[idx as u8]),
+                IndexWidth::UInt16 => encoder.write_bytes(&(idx as u16).to_le_bytes()),
+                IndexWidth::UInt32 => encoder.write_bytes(&(idx as u32).to_le_bytes()),
+                IndexWidth::UInt64 => encoder.write_bytes(&idx.to_le_bytes()),
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    fn push(&mut self, value: Value) {
+        let idx = if is_nullable(&self.inner_type) {
+            match value {
+                Value::Nullable(None) => 0,
+                Value::Nullable(Some(inner)) => self.intern(*inner),
+                other => self.intern(other),
+            }
+        } else {
+            self.intern(value)
+        };
+        self.index.push(idx);
+    }
+
+    fn at(&self, index: usize) -> ValueRef {
+        let dict_index = self.index[index] as usize;
+        if is_nullable(&self.inner_type) {
+            return if dict_index == 0 {
+                ValueRef::Nullable(None)
+            } else {
+                ValueRef::Nullable(Some(Box::new(ValueRef::from(&self.dictionary[dict_index]))))
+            };
+        }
+        ValueRef::from(&self.dictionary[dict_index])
+    }
+
+    fn clone_instance(&self) -> BoxColumnData {
+        Box::new(Self {
+            inner_type: self.inner_type.clone(),
+            dictionary: self.dictionary.clone(),
+            positions: self.positions.clone(),
+            index: self.index.clone(),
+        })
+    }
+}
+
+/// Whether `sql_type` is `Nullable(_)`.
+fn is_nullable(sql_type: &SqlType) -> bool {
+    match sql_type {
+        SqlType::Nullable(_) => true,
+        _ => false,
+    }
+}
+
+/// Strips one level of `Nullable`, since the dictionary only ever stores
+/// plain (non-null) values of the wrapped type.
+fn base_type(sql_type: &SqlType) -> &SqlType {
+    match sql_type {
+        SqlType::Nullable(inner) => inner,
+        other => other,
+    }
+}
+
+/// The default value of `base_type`, used to seed dictionary slot 0 for
+/// `LowCardinality(Nullable(_))` columns; index 0 conventionally stands for
+/// `NULL`, matching how a real ClickHouse server lays out the dictionary.
+fn default_value(base_type: &SqlType, tz: Tz) -> Value {
+    match base_type {
+        SqlType::UInt8 => Value::UInt8(0),
+        SqlType::UInt16 => Value::UInt16(0),
+        SqlType::UInt32 => Value::UInt32(0),
+        SqlType::UInt64 => Value::UInt64(0),
+        SqlType::Int8 => Value::Int8(0),
+        SqlType::Int16 => Value::Int16(0),
+        SqlType::Int32 => Value::Int32(0),
+        SqlType::Int64 => Value::Int64(0),
+        SqlType::Float32 => Value::Float32(0.0),
+        SqlType::Float64 => Value::Float64(0.0),
+        SqlType::Date => Value::Date(0, tz),
+        SqlType::DateTime(_) => Value::DateTime(0, tz),
+        SqlType::String | SqlType::FixedString(_) => Value::String(Vec::new().into()),
+        other => unimplemented!("LowCardinality(Nullable({:?})) is not supported yet", other),
+    }
+}
+
+fn read_u8<T: ReadEx>(reader: &mut T) -> Result<u8, Error> {
+    let mut buf = [0_u8; 1];
+    reader.read_bytes(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u16<T: ReadEx>(reader: &mut T) -> Result<u16, Error> {
+    let mut buf = [0_u8; 2];
+    reader.read_bytes(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u32<T: ReadEx>(reader: &mut T) -> Result<u32, Error> {
+    let mut buf = [0_u8; 4];
+    reader.read_bytes(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<T: ReadEx>(reader: &mut T) -> Result<u64, Error> {
+    let mut buf = [0_u8; 8];
+    reader.read_bytes(&mut buf)?;
+    Ok(u64::from_le_bytes(buf.try_into().unwrap()))
+}
+
+/// Serializes one dictionary entry the same way a plain (non-`LowCardinality`)
+/// column of `value`'s type would write it on the wire, so a dictionary of
+/// e.g. `UInt32`s or `DateTime`s round-trips with a real ClickHouse server
+/// instead of being degraded to text.
+fn write_inner_value(encoder: &mut Encoder, value: &Value) {
+    match value {
+        Value::UInt8(v) => encoder.write_bytes(&[*v]),
+        Value::UInt16(v) => encoder.write_bytes(&v.to_le_bytes()),
+        Value::UInt32(v) => encoder.write_bytes(&v.to_le_bytes()),
+        Value::UInt64(v) => encoder.write_bytes(&v.to_le_bytes()),
+        Value::Int8(v) => encoder.write_bytes(&v.to_le_bytes()),
+        Value::Int16(v) => encoder.write_bytes(&v.to_le_bytes()),
+        Value::Int32(v) => encoder.write_bytes(&v.to_le_bytes()),
+        Value::Int64(v) => encoder.write_bytes(&v.to_le_bytes()),
+        Value::Float32(v) => encoder.write_bytes(&v.to_le_bytes()),
+        Value::Float64(v) => encoder.write_bytes(&v.to_le_bytes()),
+        Value::Date(days, _) => encoder.write_bytes(&days.to_le_bytes()),
+        Value::DateTime(secs, _) => encoder.write_bytes(&secs.to_le_bytes()),
+        Value::String(bytes) => {
+            encoder.uvarint(bytes.len() as u64);
+            encoder.write_bytes(bytes);
+        }
+        other => unimplemented!(
+            "LowCardinality dictionary values of type {:?} are not supported yet",
+            other
+        ),
+    }
+}
+
+/// Encodes `value` the same way `write_inner_value` would, so the bytes can
+/// be used as an exact (lossless) dedup key in `positions`.
+fn encode_inner_value(value: &Value) -> Vec<u8> {
+    let mut encoder = Encoder::new();
+    write_inner_value(&mut encoder, value);
+    encoder.get_buffer_ref().clone()
+}
+
+fn read_inner_value<T: ReadEx>(
+    reader: &mut T,
+    inner_type: &SqlType,
+    tz: Tz,
+) -> Result<Value, Error> {
+    match inner_type {
+        SqlType::UInt8 => Ok(Value::UInt8(read_u8(reader)?)),
+        SqlType::UInt16 => Ok(Value::UInt16(read_u16(reader)?)),
+        SqlType::UInt32 => Ok(Value::UInt32(read_u32(reader)?)),
+        SqlType::UInt64 => Ok(Value::UInt64(read_u64(reader)?)),
+        SqlType::Int8 => Ok(Value::Int8(read_u8(reader)? as i8)),
+        SqlType::Int16 => Ok(Value::Int16(read_u16(reader)? as i16)),
+        SqlType::Int32 => Ok(Value::Int32(read_u32(reader)? as i32)),
+        SqlType::Int64 => Ok(Value::Int64(read_u64(reader)? as i64)),
+        SqlType::Float32 => Ok(Value::Float32(f32::from_bits(read_u32(reader)?))),
+        SqlType::Float64 => Ok(Value::Float64(f64::from_bits(read_u64(reader)?))),
+        SqlType::Date => Ok(Value::Date(read_u16(reader)?, tz)),
+        SqlType::DateTime(_) => Ok(Value::DateTime(read_u32(reader)?, tz)),
+        SqlType::String | SqlType::FixedString(_) => {
+            let len = reader.read_uvarint()? as usize;
+            let mut bytes = vec![0_u8; len];
+            reader.read_bytes(&mut bytes)?;
+            Ok(Value::String(bytes.into()))
+        }
+        other => Err(Error::Other(
+            format!("unsupported LowCardinality inner type: {:?}", other).into(),
+        )),
+    }
+}
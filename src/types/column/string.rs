@@ -0,0 +1,141 @@
+use crate::{
+    binary::{Encoder, ReadEx},
+    errors::Error,
+    types::{column::column_data::BoxColumnData, SqlType, Value, ValueRef},
+};
+
+use super::column_data::ColumnData;
+
+/// View-style backing store for `String` columns: one growable byte buffer
+/// plus a table of end offsets, so `at` can hand back a slice into `buffer`
+/// instead of allocating a `String` per row.
+pub(crate) struct StringViewColumnData {
+    buffer: Vec<u8>,
+    /// `offsets[i]` is the end (exclusive) of row `i`'s bytes in `buffer`;
+    /// row `i`'s start is `offsets[i - 1]` (or 0 for the first row).
+    offsets: Vec<usize>,
+}
+
+impl StringViewColumnData {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buffer: Vec::new(),
+            offsets: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub(crate) fn load<T: ReadEx>(reader: &mut T, size: usize) -> Result<Self, Error> {
+        let mut instance = Self::with_capacity(size);
+
+        for _ in 0..size {
+            let len = reader.read_uvarint()? as usize;
+            let old_len = instance.buffer.len();
+            instance.buffer.resize(old_len + len, 0_u8);
+            reader.read_bytes(&mut instance.buffer[old_len..old_len + len])?;
+            instance.offsets.push(instance.buffer.len());
+        }
+
+        Ok(instance)
+    }
+
+    /// Total number of string bytes currently stored (excludes length
+    /// prefixes), so callers can pre-size downstream buffers.
+    pub fn total_bytes_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Capacity of the backing byte buffer.
+    pub fn total_buffer_len(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    fn start(&self, index: usize) -> usize {
+        if index == 0 {
+            0
+        } else {
+            self.offsets[index - 1]
+        }
+    }
+}
+
+impl ColumnData for StringViewColumnData {
+    fn sql_type(&self) -> SqlType {
+        SqlType::String
+    }
+
+    fn save(&self, encoder: &mut Encoder, start: usize, end: usize) {
+        for index in start..end {
+            let value = &self.buffer[self.start(index)..self.offsets[index]];
+            encoder.uvarint(value.len() as u64);
+            encoder.write_bytes(value);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    fn push(&mut self, value: Value) {
+        let bs: String = String::from(value);
+        self.buffer.extend_from_slice(bs.as_bytes());
+        self.offsets.push(self.buffer.len());
+    }
+
+    fn at(&self, index: usize) -> ValueRef {
+        let value = &self.buffer[self.start(index)..self.offsets[index]];
+        ValueRef::String(value)
+    }
+
+    fn clone_instance(&self) -> BoxColumnData {
+        Box::new(Self {
+            buffer: self.buffer.clone(),
+            offsets: self.offsets.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_push_and_at() {
+        let mut instance = StringViewColumnData::with_capacity(3);
+        instance.push(Value::String(b"foo".to_vec().into()));
+        instance.push(Value::String(Vec::new().into()));
+        instance.push(Value::String(b"bar".to_vec().into()));
+
+        assert_eq!(instance.len(), 3);
+        assert_eq!(instance.at(0).as_bytes().unwrap(), b"foo");
+        assert_eq!(instance.at(1).as_bytes().unwrap(), b"");
+        assert_eq!(instance.at(2).as_bytes().unwrap(), b"bar");
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let mut instance = StringViewColumnData::with_capacity(2);
+        instance.push(Value::String(b"hello".to_vec().into()));
+        instance.push(Value::String(b"world!".to_vec().into()));
+
+        let mut encoder = Encoder::new();
+        instance.save(&mut encoder, 0, instance.len());
+
+        let mut cursor = Cursor::new(encoder.get_buffer_ref().clone());
+        let loaded = StringViewColumnData::load(&mut cursor, 2).unwrap();
+
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.at(0).as_bytes().unwrap(), b"hello");
+        assert_eq!(loaded.at(1).as_bytes().unwrap(), b"world!");
+    }
+
+    #[test]
+    fn test_total_bytes_len() {
+        let mut instance = StringViewColumnData::with_capacity(2);
+        instance.push(Value::String(b"ab".to_vec().into()));
+        instance.push(Value::String(b"cde".to_vec().into()));
+
+        assert_eq!(instance.total_bytes_len(), 5);
+    }
+}